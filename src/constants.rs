@@ -0,0 +1,7 @@
+/// Vendor VCM auto-focus MCU firmware blob, for boards that don't ship their own.
+///
+/// Populate this with the binary from the module vendor's SDK before enabling the
+/// `af-default-firmware` feature; it is intentionally left empty here since the blob is
+/// redistributed under the vendor's own terms, not this crate's license.
+#[cfg(feature = "af-default-firmware")]
+pub const OV5640_AF_DEFAULT_FIRMWARE: &[u8] = &[];
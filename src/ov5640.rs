@@ -3,21 +3,111 @@ use embedded_hal::{
     digital::v2::OutputPin,
 };
 
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c;
+
 use core::convert::TryInto;
 
 use crate::constants::*;
 
+/// Output polarity control, see [`PolarityConfig`].
+const OV5640_REG_POLARITY_CTRL: u16 = 0x4740;
+const OV5640_POLARITY_CTRL_MASK: u8 = (1 << 5) | (1 << 4) | (1 << 1) | (1 << 0);
+
+/// Compression control 07: bits `0..=5` quantization scale, bit 6 terminate-on-last-byte. See [`JpegMode`].
+const OV5640_REG_JPEG_CTRL: u16 = 0x4407;
+const OV5640_JPEG_CTRL_TERMINATE_ON_LAST_BYTE: u8 = 1 << 6;
+
+/// YUV422 byte order feeding the JPEG compressor. See [`JpegMode`].
+const OV5640_REG_YUV422_BYTE_ORDER: u16 = 0x4300;
+
+/// System reset control, bit 5 holds the VCM auto-focus MCU in reset.
+const OV5640_REG_SYSTEM_RESET00: u16 = 0x3000;
+const OV5640_SYSTEM_RESET00_MCU_RESET: u8 = 1 << 5;
+
+/// Start address the AF firmware blob is streamed to, one byte per incrementing 16-bit address.
+const OV5640_AF_FW_BASE_ADDR: u16 = 0x8000;
+
+/// AF firmware command/ack mailbox, cleared before releasing the MCU out of reset.
+const OV5640_REG_FW_CMD_MAIN: u16 = 0x3022;
+const OV5640_REG_FW_CMD_ACK: u16 = 0x3023;
+const OV5640_REG_FW_CMD_PARA0: u16 = 0x3024;
+const OV5640_REG_FW_CMD_PARA1: u16 = 0x3025;
+const OV5640_REG_FW_STATUS: u16 = 0x3029;
+const OV5640_FW_STATUS_READY: u8 = 0x70;
+const OV5640_FW_STATUS_FOCUSED: u8 = 0x10;
+
+const OV5640_AF_CMD_TRIGGER_SINGLE_FOCUS: u8 = 0x03;
+const OV5640_AF_CMD_CONTINUOUS_FOCUS: u8 = 0x04;
+const OV5640_AF_CMD_PAUSE_FOCUS: u8 = 0x06;
+
+/// How many times to poll [`OV5640_REG_FW_STATUS`] for [`OV5640_FW_STATUS_READY`] before giving up.
+const OV5640_AF_FW_READY_POLL_ATTEMPTS: u32 = 1000;
+
+/// Special Digital Effects control 0, bit 0 enables the SDE block the brightness/contrast/
+/// saturation/hue registers below live in.
+const OV5640_REG_SDE_CTRL0: u16 = 0x5580;
+const OV5640_SDE_CTRL0_ENABLE: u8 = 1 << 0;
+
+const OV5640_REG_SDE_HUE_COS: u16 = 0x5581;
+const OV5640_REG_SDE_HUE_SIN: u16 = 0x5582;
+const OV5640_REG_SDE_SATURATION_U: u16 = 0x5583;
+const OV5640_REG_SDE_SATURATION_V: u16 = 0x5584;
+const OV5640_REG_SDE_CONTRAST: u16 = 0x5586;
+const OV5640_REG_SDE_BRIGHTNESS: u16 = 0x5587;
+const OV5640_REG_SDE_BRIGHTNESS_SIGN: u16 = 0x5588;
+const OV5640_SDE_BRIGHTNESS_SIGN_NEGATIVE: u8 = 1 << 3;
+
+const OV5640_REG_AWB_MANUAL_CTRL: u16 = 0x3406;
+const OV5640_REG_AWB_CTRL: u16 = 0x5183;
+
+/// Timing control 20/21: vertical flip and horizontal mirror each need bits 1 and 2 set together,
+/// or the sensor's binning compensation disagrees with the readout direction.
+const OV5640_REG_TIMING_TC_20: u16 = 0x3820;
+const OV5640_REG_TIMING_TC_21: u16 = 0x3821;
+const OV5640_TIMING_FLIP_MIRROR_MASK: u8 = 0b0000_0110;
+
+/// PLL control registers, see [`ClockConfig`]/[`Ov5640::set_clock`].
+const OV5640_REG_PLL_CTRL_3034: u16 = 0x3034;
+const OV5640_PLL_CTRL_3034_8_BIT_MODE: u8 = 0x1a;
+/// System clock divider in bits `4..=7`; bits `0..=3` are the MIPI divider, which this DVP-only
+/// driver never touches, so they're preserved via `modify_reg` rather than zeroed.
+const OV5640_REG_PLL_CTRL_SYS_DIV: u16 = 0x3035;
+const OV5640_PLL_CTRL_SYS_DIV_MASK: u8 = 0b1111_0000;
+
+const OV5640_REG_PLL_CTRL_MULTIPLIER: u16 = 0x3036;
+const OV5640_REG_PLL_CTRL_PRE_DIV: u16 = 0x3037;
+const OV5640_PLL_CTRL_PRE_DIV_MASK: u8 = 0b0000_1111;
+const OV5640_PLL_CTRL_ROOT_DIV_MASK: u8 = 0b0001_0000;
+
+/// SCLK root divider in bits `2..=3`, PCLK root divider in bits `0..=1`. Only the PCLK divider
+/// feeds into `set_clock`'s pixel-clock math; the SCLK divider doesn't affect the output frame
+/// rate, so it's held at a fixed conservative divide-by-2 instead of being solved for.
+const OV5640_REG_SYSTEM_ROOT_DIVIDER: u16 = 0x3108;
+const OV5640_SYSTEM_ROOT_DIVIDER_PCLK_MASK: u8 = 0b0000_0011;
+const OV5640_SYSTEM_ROOT_DIVIDER_SCLK_MASK: u8 = 0b0000_1100;
+const OV5640_SYSTEM_ROOT_DIVIDER_SCLK_DIV2: u8 = 0b0000_0100;
+
 #[derive(Debug)]
 pub enum SccbError<I2CE> {
     I2c(I2CE),
     InvalidId(u8),
     Gpio,
+    /// The AF MCU never reported ready after a firmware download.
+    AfFirmwareTimeout,
+    /// No PLL divider/multiplier combination reaches the requested [`ClockConfig::target_fps`].
+    UnreachableFrameRate,
 }
 
 pub struct Ov5640<I2C, PWDN, RST> {
     i2c: I2C,
     pwdn: PWDN,
     rst: RST,
+    /// Format passed to [`Ov5640::init`], kept around so [`Ov5640::set_flip`]/[`Ov5640::set_mirror`]
+    /// can recompute the Bayer order for `Format::Raw` without the caller having to remember it.
+    format: Option<Format>,
+    flip: bool,
+    mirror: bool,
 }
 
 pub enum Resolution {
@@ -32,12 +122,42 @@ pub enum Resolution {
     Qsxga2592_1944,
 }
 
+impl Resolution {
+    /// Total pixels per frame, used by [`Ov5640::set_clock`] to size the PLL for a target frame rate.
+    fn total_pixels(&self) -> u32 {
+        let (width, height) = match self {
+            Resolution::Qcifz176_144 => (176, 144),
+            Resolution::Qvga320_240 => (320, 240),
+            Resolution::Vga640_480 => (640, 480),
+            Resolution::Ntsc720_480 => (720, 480),
+            Resolution::Pal720_576 => (720, 576),
+            Resolution::Xga1024_768 => (1024, 768),
+            Resolution::P720_1280_720 => (1280, 720),
+            Resolution::P1080_1920_1080 => (1920, 1080),
+            Resolution::Qsxga2592_1944 => (2592, 1944),
+        };
+        width * height
+    }
+}
+
 pub enum Format {
     Raw(RawOrder),
     Rgb565(Rgb565Order),
     Yuv422(Yuv422Order),
+    Jpeg(JpegMode),
+}
+
+/// Compressed JPEG output, fed from the ISP's YUV422 path into the hardware compressor.
+///
+/// Frames are variable length: the sensor is configured to terminate the frame on its last
+/// compressed data byte (register `0x4407` bit 6) rather than padding to a fixed size, so the
+/// DCMI/DMA side must detect end-of-frame from HREF/VSYNC deassertion instead of a byte count.
+pub struct JpegMode {
+    /// Quantization scale, low 6 bits of register `0x4407`. Lower values mean higher quality.
+    pub quality: u8,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum RawOrder {
     SBGGR8, // BGBG... / GRGR...0x0,
     SGBRG8, // GBGB... / RGRG...0x1,
@@ -61,12 +181,119 @@ pub enum Yuv422Order {
     Vyuy,
 }
 
+/// Active level of a digital sync signal such as VSYNC or HREF.
+pub enum Polarity {
+    ActiveHigh,
+    ActiveLow,
+}
+
+/// Edge on which the host should sample PCLK, mirrored by the sensor's own sampling edge.
+pub enum PixelClockPolarity {
+    SampleOnRisingEdge,
+    SampleOnFallingEdge,
+}
+
+/// Output sync/clock polarity for parallel (DCMI-style) capture, written to register `0x4740`.
+pub struct PolarityConfig {
+    pub vsync: Polarity,
+    pub href: Polarity,
+    pub pclk: PixelClockPolarity,
+    pub gate_pclk_by_href: bool,
+}
+
+impl PolarityConfig {
+    fn to_bits(&self) -> u8 {
+        let mut bits = 0u8;
+        if let Polarity::ActiveLow = self.vsync {
+            bits |= 1 << 1;
+        }
+        if let Polarity::ActiveLow = self.href {
+            bits |= 1 << 0;
+        }
+        if let PixelClockPolarity::SampleOnFallingEdge = self.pclk {
+            bits |= 1 << 5;
+        }
+        if self.gate_pclk_by_href {
+            bits |= 1 << 4;
+        }
+        bits
+    }
+}
+
+/// XCLK input and desired frame rate, used by [`Ov5640::set_clock`] to derive the PLL
+/// multiplier/dividers for the resolution passed alongside it.
+pub struct ClockConfig {
+    pub xclk_hz: u32,
+    pub target_fps: u8,
+}
+
+/// One of the 8 fractional pre-dividers register `0x3037` bits `0..=3` can select, stored as
+/// twice the actual divisor so the 1.5x step stays integral.
+const OV5640_PLL_PRE_DIVIDERS_X2: [u32; 8] = [2, 3, 4, 5, 6, 8, 12, 16];
+
+struct PllSolution {
+    multiplier: u8,
+    pre_div_reg: u8,
+    root_div_reg: u8,
+    sys_div: u8,
+    pclk_div_reg: u8,
+}
+
+impl PllSolution {
+    fn pclk_hz(&self, xclk_hz: u32) -> u64 {
+        let vco_hz =
+            xclk_hz as u64 * 2 * self.multiplier as u64 / OV5640_PLL_PRE_DIVIDERS_X2[self.pre_div_reg as usize] as u64;
+        let root_div = if self.root_div_reg == 0 { 1 } else { 2 };
+        let pclk_div = 1u64 << self.pclk_div_reg;
+        vco_hz / root_div / self.sys_div as u64 / pclk_div
+    }
+}
+
+/// Search the PLL parameter space for the combination whose resulting PCLK is closest to
+/// `target_pclk_hz`, accepting it only within 1% of the target.
+fn solve_pll(xclk_hz: u32, target_pclk_hz: u64) -> Option<PllSolution> {
+    let mut best: Option<(PllSolution, u64)> = None;
+
+    for pre_div_reg in 0..OV5640_PLL_PRE_DIVIDERS_X2.len() as u8 {
+        for root_div_reg in 0..=1 {
+            for sys_div in 1..=15u8 {
+                for pclk_div_reg in 0..=3u8 {
+                    for multiplier in 4..=252u8 {
+                        let candidate = PllSolution {
+                            multiplier,
+                            pre_div_reg,
+                            root_div_reg,
+                            sys_div,
+                            pclk_div_reg,
+                        };
+                        let pclk_hz = candidate.pclk_hz(xclk_hz);
+                        let error = pclk_hz.abs_diff(target_pclk_hz);
+
+                        let is_better = match &best {
+                            Some((_, best_error)) => error < *best_error,
+                            None => true,
+                        };
+                        if is_better {
+                            best = Some((candidate, error));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    best.filter(|(_, error)| *error * 100 <= target_pclk_hz)
+        .map(|(solution, _)| solution)
+}
+
 impl Format {
     fn format_bits(&self) -> u8 {
         match self {
             Format::Raw(order) => order.to_hex(),
             Format::Rgb565(order) => 0x60 | order.to_hex(),
             Format::Yuv422(order) => 0x30 | order.to_hex(),
+            // the compressor consumes the ISP's YUV422 byte stream, so the YUYV byte order applies here too
+            Format::Jpeg(_) => 0x30 | Yuv422Order::Yuyv.to_hex(),
         }
     }
 
@@ -75,12 +302,13 @@ impl Format {
             Format::Raw(_) => OV5640_FMT_MUX_RAW_DPC,
             Format::Rgb565(_) => OV5640_FMT_MUX_RGB,
             Format::Yuv422(_) => OV5640_FMT_MUX_YUV422,
+            Format::Jpeg(_) => OV5640_FMT_MUX_YUV422,
         }
     }
 }
 
 impl RawOrder {
-    fn to_hex(&self) -> u8 {
+    fn to_hex(self) -> u8 {
         match self {
             RawOrder::SBGGR8 => 0,
             RawOrder::SGBRG8 => 1,
@@ -88,6 +316,26 @@ impl RawOrder {
             RawOrder::SRGGB8 => 3,
         }
     }
+
+    /// Bayer order after swapping the sensor's row pair, as happens under a vertical flip.
+    fn flipped(self) -> Self {
+        match self {
+            RawOrder::SBGGR8 => RawOrder::SGRBG8,
+            RawOrder::SGRBG8 => RawOrder::SBGGR8,
+            RawOrder::SGBRG8 => RawOrder::SRGGB8,
+            RawOrder::SRGGB8 => RawOrder::SGBRG8,
+        }
+    }
+
+    /// Bayer order after swapping the sensor's column pair, as happens under a horizontal mirror.
+    fn mirrored(self) -> Self {
+        match self {
+            RawOrder::SBGGR8 => RawOrder::SGBRG8,
+            RawOrder::SGBRG8 => RawOrder::SBGGR8,
+            RawOrder::SGRBG8 => RawOrder::SRGGB8,
+            RawOrder::SRGGB8 => RawOrder::SGRBG8,
+        }
+    }
 }
 
 impl Rgb565Order {
@@ -124,10 +372,22 @@ where
     where
         I2C: Read + Write,
     {
-        Ov5640 { i2c, pwdn, rst }
+        Ov5640 {
+            i2c,
+            pwdn,
+            rst,
+            format: None,
+            flip: false,
+            mirror: false,
+        }
     }
 
-    pub fn init(&mut self, format: Format, resolution: Resolution) -> Result<(), SccbError<E>> {
+    pub fn init(
+        &mut self,
+        format: Format,
+        resolution: Resolution,
+        polarity: Option<PolarityConfig>,
+    ) -> Result<(), SccbError<E>> {
         let slave_id = self.read_reg(OV5640_REG_ID)?;
         if slave_id != OV5640_ID {
             return Err(SccbError::InvalidId(slave_id));
@@ -155,9 +415,155 @@ where
         self.write_reg(OV5640_REG_FORMAT_00, format.format_bits())?;
         self.write_reg(OV5640_REG_ISP_FORMAT_MUX_CTRL, format.mux_bits())?;
 
+        if let Format::Jpeg(JpegMode { quality }) = &format {
+            self.write_reg(OV5640_REG_YUV422_BYTE_ORDER, Yuv422Order::Yuyv.to_hex())?;
+            self.write_reg(
+                OV5640_REG_JPEG_CTRL,
+                OV5640_JPEG_CTRL_TERMINATE_ON_LAST_BYTE | (quality & 0x3f),
+            )?;
+        }
+
+        if let Some(polarity) = polarity {
+            self.set_polarity(&polarity)?;
+        }
+
+        self.format = Some(format);
+        self.flip = false;
+        self.mirror = false;
+
         Ok(())
     }
 
+    /// Align the sensor's VSYNC/HREF/PCLK polarity with the host's parallel capture peripheral.
+    pub fn set_polarity(&mut self, polarity: &PolarityConfig) -> Result<(), SccbError<E>> {
+        self.modify_reg(
+            OV5640_REG_POLARITY_CTRL,
+            OV5640_POLARITY_CTRL_MASK,
+            polarity.to_bits(),
+        )
+    }
+
+    /// Brightness offset, roughly -128..=127, applied as the SDE's signed magnitude.
+    pub fn set_brightness(&mut self, value: i8) -> Result<(), SccbError<E>> {
+        self.enable_sde()?;
+        self.write_reg(OV5640_REG_SDE_BRIGHTNESS, value.unsigned_abs())?;
+        self.update_bits(
+            OV5640_REG_SDE_BRIGHTNESS_SIGN,
+            OV5640_SDE_BRIGHTNESS_SIGN_NEGATIVE,
+            value < 0,
+        )
+    }
+
+    pub fn set_contrast(&mut self, value: u8) -> Result<(), SccbError<E>> {
+        self.enable_sde()?;
+        self.write_reg(OV5640_REG_SDE_CONTRAST, value)
+    }
+
+    /// Saturation gain applied equally to the U and V planes.
+    pub fn set_saturation(&mut self, value: u8) -> Result<(), SccbError<E>> {
+        self.enable_sde()?;
+        self.write_reg(OV5640_REG_SDE_SATURATION_U, value)?;
+        self.write_reg(OV5640_REG_SDE_SATURATION_V, value)
+    }
+
+    /// Raw SDE hue rotation coefficients, as the sensor expects them: `cos`/`sin` of the desired
+    /// hue angle scaled to an `i8`, rather than an angle in degrees.
+    pub fn set_hue(&mut self, cos: i8, sin: i8) -> Result<(), SccbError<E>> {
+        self.enable_sde()?;
+        self.write_reg(OV5640_REG_SDE_HUE_COS, cos as u8)?;
+        self.write_reg(OV5640_REG_SDE_HUE_SIN, sin as u8)
+    }
+
+    fn enable_sde(&mut self) -> Result<(), SccbError<E>> {
+        self.update_bits(OV5640_REG_SDE_CTRL0, OV5640_SDE_CTRL0_ENABLE, true)
+    }
+
+    pub fn set_auto_white_balance(&mut self, enabled: bool) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_AWB_MANUAL_CTRL, if enabled { 0x00 } else { 0x01 })?;
+        self.write_reg(OV5640_REG_AWB_CTRL, if enabled { 0x01 } else { 0x00 })
+    }
+
+    /// Flip the output vertically. For `Format::Raw`, also rewrites the Bayer order so
+    /// downstream debayering stays correct.
+    pub fn set_flip(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        self.flip = on;
+        self.write_flip_mirror_timing()?;
+        self.write_raw_order()
+    }
+
+    /// Mirror the output horizontally. For `Format::Raw`, also rewrites the Bayer order so
+    /// downstream debayering stays correct.
+    pub fn set_mirror(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        self.mirror = on;
+        self.write_flip_mirror_timing()?;
+        self.write_raw_order()
+    }
+
+    fn write_flip_mirror_timing(&mut self) -> Result<(), SccbError<E>> {
+        self.update_bits(
+            OV5640_REG_TIMING_TC_20,
+            OV5640_TIMING_FLIP_MIRROR_MASK,
+            self.flip,
+        )?;
+        self.update_bits(
+            OV5640_REG_TIMING_TC_21,
+            OV5640_TIMING_FLIP_MIRROR_MASK,
+            self.mirror,
+        )
+    }
+
+    fn write_raw_order(&mut self) -> Result<(), SccbError<E>> {
+        let effective_order = if let Some(Format::Raw(order)) = &self.format {
+            let mut order = *order;
+            if self.flip {
+                order = order.flipped();
+            }
+            if self.mirror {
+                order = order.mirrored();
+            }
+            Some(order)
+        } else {
+            None
+        };
+
+        if let Some(order) = effective_order {
+            self.write_reg(OV5640_REG_FORMAT_00, Format::Raw(order).format_bits())?;
+        }
+
+        Ok(())
+    }
+
+    /// Program the PLL so the sensor's pixel clock and frame rate match `clock` for the given
+    /// `resolution`. Returns [`SccbError::UnreachableFrameRate`] if no supported combination of
+    /// multiplier and dividers gets within 1% of the requested `target_fps`.
+    pub fn set_clock(
+        &mut self,
+        clock: &ClockConfig,
+        resolution: &Resolution,
+    ) -> Result<(), SccbError<E>> {
+        let target_pclk_hz = resolution.total_pixels() as u64 * clock.target_fps as u64;
+        let solution =
+            solve_pll(clock.xclk_hz, target_pclk_hz).ok_or(SccbError::UnreachableFrameRate)?;
+
+        self.write_reg(OV5640_REG_PLL_CTRL_3034, OV5640_PLL_CTRL_3034_8_BIT_MODE)?;
+        self.modify_reg(
+            OV5640_REG_PLL_CTRL_SYS_DIV,
+            OV5640_PLL_CTRL_SYS_DIV_MASK,
+            solution.sys_div << 4,
+        )?;
+        self.write_reg(OV5640_REG_PLL_CTRL_MULTIPLIER, solution.multiplier)?;
+        self.modify_reg(
+            OV5640_REG_PLL_CTRL_PRE_DIV,
+            OV5640_PLL_CTRL_PRE_DIV_MASK | OV5640_PLL_CTRL_ROOT_DIV_MASK,
+            solution.pre_div_reg | (solution.root_div_reg << 4),
+        )?;
+        self.modify_reg(
+            OV5640_REG_SYSTEM_ROOT_DIVIDER,
+            OV5640_SYSTEM_ROOT_DIVIDER_PCLK_MASK | OV5640_SYSTEM_ROOT_DIVIDER_SCLK_MASK,
+            solution.pclk_div_reg | OV5640_SYSTEM_ROOT_DIVIDER_SCLK_DIV2,
+        )
+    }
+
     pub fn set_rst(&mut self, on: bool) -> Result<(), SccbError<E>> {
         if on {
             self.rst.set_high().map_err(|_| SccbError::Gpio)
@@ -174,6 +580,57 @@ where
         }
     }
 
+    /// Upload the VCM auto-focus MCU firmware blob so `trigger_single_focus`/`continuous_focus`/
+    /// `pause_focus`/`focus_status` below start working. Holds the MCU in reset while streaming
+    /// `fw` to the firmware RAM starting at the AF firmware base address, clears the command
+    /// mailbox, then releases the MCU and waits for it to report ready.
+    pub fn af_firmware_download(&mut self, fw: &[u8]) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_SYSTEM_RESET00, OV5640_SYSTEM_RESET00_MCU_RESET)?;
+
+        for (offset, byte) in fw.iter().enumerate() {
+            let addr = OV5640_AF_FW_BASE_ADDR.wrapping_add(offset as u16);
+            self.write_reg(addr, *byte)?;
+        }
+
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, 0x00)?;
+        self.write_reg(OV5640_REG_FW_CMD_ACK, 0x00)?;
+        self.write_reg(OV5640_REG_FW_CMD_PARA0, 0x00)?;
+        self.write_reg(OV5640_REG_FW_CMD_PARA1, 0x00)?;
+        self.write_reg(OV5640_REG_FW_STATUS, 0x00)?;
+
+        self.write_reg(OV5640_REG_SYSTEM_RESET00, 0x00)?;
+
+        for _ in 0..OV5640_AF_FW_READY_POLL_ATTEMPTS {
+            if self.read_reg(OV5640_REG_FW_STATUS)? == OV5640_FW_STATUS_READY {
+                return Ok(());
+            }
+        }
+
+        Err(SccbError::AfFirmwareTimeout)
+    }
+
+    /// Trigger a single auto-focus pass. Returns as soon as the command is written; it does not
+    /// wait for the MCU to acknowledge it.
+    pub fn trigger_single_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_ACK, 0x01)?;
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_TRIGGER_SINGLE_FOCUS)
+    }
+
+    /// Hand focus tracking over to the MCU until [`Ov5640::pause_focus`] is called.
+    pub fn continuous_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_CONTINUOUS_FOCUS)
+    }
+
+    /// Pause whatever focus mode is currently running.
+    pub fn pause_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_PAUSE_FOCUS)
+    }
+
+    /// Whether the MCU currently considers the image in focus.
+    pub fn focus_status(&mut self) -> Result<bool, SccbError<E>> {
+        Ok(self.read_reg(OV5640_REG_FW_STATUS)? == OV5640_FW_STATUS_FOCUSED)
+    }
+
     fn write_reg(&mut self, reg: u16, val: u8) -> Result<(), SccbError<E>> {
         self.i2c
             .write(
@@ -207,7 +664,446 @@ where
         Ok(buf[0])
     }
 
+    /// Read `reg`, clear `clear_mask`, set `set_mask`, and write the result back, so independent
+    /// bitfields sharing a register don't clobber each other.
+    fn modify_reg(&mut self, reg: u16, clear_mask: u8, set_mask: u8) -> Result<(), SccbError<E>> {
+        let val = self.read_reg(reg)?;
+        self.write_reg(reg, (val & !clear_mask) | set_mask)
+    }
+
+    /// Set or clear the bits in `mask` within `reg`, leaving every other bit untouched.
+    fn update_bits(&mut self, reg: u16, mask: u8, set: bool) -> Result<(), SccbError<E>> {
+        if set {
+            self.modify_reg(reg, 0, mask)
+        } else {
+            self.modify_reg(reg, mask, 0)
+        }
+    }
+
     pub fn free(self) -> (I2C, PWDN, RST) {
         (self.i2c, self.pwdn, self.rst)
     }
 }
+
+/// Same driver as [`Ov5640`], but built on `embedded-hal-async` so the SCCB transactions can be
+/// awaited instead of blocking, e.g. alongside an async DCMI capture loop. Kept in sync with
+/// [`Ov5640`] method-for-method as new registers are added.
+#[cfg(feature = "async")]
+pub struct Ov5640Async<I2C, PWDN, RST> {
+    i2c: I2C,
+    pwdn: PWDN,
+    rst: RST,
+    /// Format passed to [`Ov5640Async::init`], kept around so [`Ov5640Async::set_flip`]/
+    /// [`Ov5640Async::set_mirror`] can recompute the Bayer order for `Format::Raw` without the
+    /// caller having to remember it.
+    format: Option<Format>,
+    flip: bool,
+    mirror: bool,
+}
+
+#[cfg(feature = "async")]
+impl<I2C, E, PWDN, RST> Ov5640Async<I2C, PWDN, RST>
+where
+    I2C: I2c<Error = E>,
+    PWDN: OutputPin,
+    RST: OutputPin,
+{
+    pub fn new(i2c: I2C, pwdn: PWDN, rst: RST) -> Self {
+        Ov5640Async {
+            i2c,
+            pwdn,
+            rst,
+            format: None,
+            flip: false,
+            mirror: false,
+        }
+    }
+
+    pub async fn init(
+        &mut self,
+        format: Format,
+        resolution: Resolution,
+        polarity: Option<PolarityConfig>,
+    ) -> Result<(), SccbError<E>> {
+        let slave_id = self.read_reg(OV5640_REG_ID).await?;
+        if slave_id != OV5640_ID {
+            return Err(SccbError::InvalidId(slave_id));
+        }
+
+        for register in OV5640_INITIAL_SETTINGS.iter() {
+            self.write_reg(register.0, register.1).await?;
+        }
+
+        for register in match resolution {
+            Resolution::Qcifz176_144 => QCIF_176_144.iter(),
+            Resolution::Qvga320_240 => QVGA_320_240.iter(),
+            Resolution::Vga640_480 => VGA_640_480.iter(),
+            Resolution::Ntsc720_480 => NTSC_720_480.iter(),
+            Resolution::Pal720_576 => PAL_720_576.iter(),
+            Resolution::Xga1024_768 => XGA_1024_768.iter(),
+            Resolution::P720_1280_720 => P720_1280_720.iter(),
+            Resolution::P1080_1920_1080 => P1080_1920_1080.iter(),
+            Resolution::Qsxga2592_1944 => QSXGA_2592_1944.iter(),
+        } {
+            self.write_reg(register.0, register.1).await?;
+        }
+
+        // configure the output format
+        self.write_reg(OV5640_REG_FORMAT_00, format.format_bits())
+            .await?;
+        self.write_reg(OV5640_REG_ISP_FORMAT_MUX_CTRL, format.mux_bits())
+            .await?;
+
+        if let Format::Jpeg(JpegMode { quality }) = &format {
+            self.write_reg(OV5640_REG_YUV422_BYTE_ORDER, Yuv422Order::Yuyv.to_hex())
+                .await?;
+            self.write_reg(
+                OV5640_REG_JPEG_CTRL,
+                OV5640_JPEG_CTRL_TERMINATE_ON_LAST_BYTE | (quality & 0x3f),
+            )
+            .await?;
+        }
+
+        if let Some(polarity) = polarity {
+            self.set_polarity(&polarity).await?;
+        }
+
+        self.format = Some(format);
+        self.flip = false;
+        self.mirror = false;
+
+        Ok(())
+    }
+
+    /// Align the sensor's VSYNC/HREF/PCLK polarity with the host's parallel capture peripheral.
+    pub async fn set_polarity(&mut self, polarity: &PolarityConfig) -> Result<(), SccbError<E>> {
+        self.modify_reg(
+            OV5640_REG_POLARITY_CTRL,
+            OV5640_POLARITY_CTRL_MASK,
+            polarity.to_bits(),
+        )
+        .await
+    }
+
+    /// Brightness offset, roughly -128..=127, applied as the SDE's signed magnitude.
+    pub async fn set_brightness(&mut self, value: i8) -> Result<(), SccbError<E>> {
+        self.enable_sde().await?;
+        self.write_reg(OV5640_REG_SDE_BRIGHTNESS, value.unsigned_abs())
+            .await?;
+        self.update_bits(
+            OV5640_REG_SDE_BRIGHTNESS_SIGN,
+            OV5640_SDE_BRIGHTNESS_SIGN_NEGATIVE,
+            value < 0,
+        )
+        .await
+    }
+
+    pub async fn set_contrast(&mut self, value: u8) -> Result<(), SccbError<E>> {
+        self.enable_sde().await?;
+        self.write_reg(OV5640_REG_SDE_CONTRAST, value).await
+    }
+
+    /// Saturation gain applied equally to the U and V planes.
+    pub async fn set_saturation(&mut self, value: u8) -> Result<(), SccbError<E>> {
+        self.enable_sde().await?;
+        self.write_reg(OV5640_REG_SDE_SATURATION_U, value).await?;
+        self.write_reg(OV5640_REG_SDE_SATURATION_V, value).await
+    }
+
+    /// Raw SDE hue rotation coefficients, as the sensor expects them: `cos`/`sin` of the desired
+    /// hue angle scaled to an `i8`, rather than an angle in degrees.
+    pub async fn set_hue(&mut self, cos: i8, sin: i8) -> Result<(), SccbError<E>> {
+        self.enable_sde().await?;
+        self.write_reg(OV5640_REG_SDE_HUE_COS, cos as u8).await?;
+        self.write_reg(OV5640_REG_SDE_HUE_SIN, sin as u8).await
+    }
+
+    async fn enable_sde(&mut self) -> Result<(), SccbError<E>> {
+        self.update_bits(OV5640_REG_SDE_CTRL0, OV5640_SDE_CTRL0_ENABLE, true)
+            .await
+    }
+
+    pub async fn set_auto_white_balance(&mut self, enabled: bool) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_AWB_MANUAL_CTRL, if enabled { 0x00 } else { 0x01 })
+            .await?;
+        self.write_reg(OV5640_REG_AWB_CTRL, if enabled { 0x01 } else { 0x00 })
+            .await
+    }
+
+    /// Flip the output vertically. For `Format::Raw`, also rewrites the Bayer order so
+    /// downstream debayering stays correct.
+    pub async fn set_flip(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        self.flip = on;
+        self.write_flip_mirror_timing().await?;
+        self.write_raw_order().await
+    }
+
+    /// Mirror the output horizontally. For `Format::Raw`, also rewrites the Bayer order so
+    /// downstream debayering stays correct.
+    pub async fn set_mirror(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        self.mirror = on;
+        self.write_flip_mirror_timing().await?;
+        self.write_raw_order().await
+    }
+
+    async fn write_flip_mirror_timing(&mut self) -> Result<(), SccbError<E>> {
+        self.update_bits(
+            OV5640_REG_TIMING_TC_20,
+            OV5640_TIMING_FLIP_MIRROR_MASK,
+            self.flip,
+        )
+        .await?;
+        self.update_bits(
+            OV5640_REG_TIMING_TC_21,
+            OV5640_TIMING_FLIP_MIRROR_MASK,
+            self.mirror,
+        )
+        .await
+    }
+
+    async fn write_raw_order(&mut self) -> Result<(), SccbError<E>> {
+        let effective_order = if let Some(Format::Raw(order)) = &self.format {
+            let mut order = *order;
+            if self.flip {
+                order = order.flipped();
+            }
+            if self.mirror {
+                order = order.mirrored();
+            }
+            Some(order)
+        } else {
+            None
+        };
+
+        if let Some(order) = effective_order {
+            self.write_reg(OV5640_REG_FORMAT_00, Format::Raw(order).format_bits())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Program the PLL so the sensor's pixel clock and frame rate match `clock` for the given
+    /// `resolution`. Returns [`SccbError::UnreachableFrameRate`] if no supported combination of
+    /// multiplier and dividers gets within 1% of the requested `target_fps`.
+    pub async fn set_clock(
+        &mut self,
+        clock: &ClockConfig,
+        resolution: &Resolution,
+    ) -> Result<(), SccbError<E>> {
+        let target_pclk_hz = resolution.total_pixels() as u64 * clock.target_fps as u64;
+        let solution =
+            solve_pll(clock.xclk_hz, target_pclk_hz).ok_or(SccbError::UnreachableFrameRate)?;
+
+        self.write_reg(OV5640_REG_PLL_CTRL_3034, OV5640_PLL_CTRL_3034_8_BIT_MODE)
+            .await?;
+        self.modify_reg(
+            OV5640_REG_PLL_CTRL_SYS_DIV,
+            OV5640_PLL_CTRL_SYS_DIV_MASK,
+            solution.sys_div << 4,
+        )
+        .await?;
+        self.write_reg(OV5640_REG_PLL_CTRL_MULTIPLIER, solution.multiplier)
+            .await?;
+        self.modify_reg(
+            OV5640_REG_PLL_CTRL_PRE_DIV,
+            OV5640_PLL_CTRL_PRE_DIV_MASK | OV5640_PLL_CTRL_ROOT_DIV_MASK,
+            solution.pre_div_reg | (solution.root_div_reg << 4),
+        )
+        .await?;
+        self.modify_reg(
+            OV5640_REG_SYSTEM_ROOT_DIVIDER,
+            OV5640_SYSTEM_ROOT_DIVIDER_PCLK_MASK | OV5640_SYSTEM_ROOT_DIVIDER_SCLK_MASK,
+            solution.pclk_div_reg | OV5640_SYSTEM_ROOT_DIVIDER_SCLK_DIV2,
+        )
+        .await
+    }
+
+    pub fn set_rst(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        if on {
+            self.rst.set_high().map_err(|_| SccbError::Gpio)
+        } else {
+            self.rst.set_low().map_err(|_| SccbError::Gpio)
+        }
+    }
+
+    pub fn set_pwdn(&mut self, on: bool) -> Result<(), SccbError<E>> {
+        if on {
+            self.pwdn.set_high().map_err(|_| SccbError::Gpio)
+        } else {
+            self.pwdn.set_low().map_err(|_| SccbError::Gpio)
+        }
+    }
+
+    /// Upload the VCM auto-focus MCU firmware blob so `trigger_single_focus`/`continuous_focus`/
+    /// `pause_focus`/`focus_status` below start working. Holds the MCU in reset while streaming
+    /// `fw` to the firmware RAM starting at the AF firmware base address, clears the command
+    /// mailbox, then releases the MCU and waits for it to report ready.
+    pub async fn af_firmware_download(&mut self, fw: &[u8]) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_SYSTEM_RESET00, OV5640_SYSTEM_RESET00_MCU_RESET)
+            .await?;
+
+        for (offset, byte) in fw.iter().enumerate() {
+            let addr = OV5640_AF_FW_BASE_ADDR.wrapping_add(offset as u16);
+            self.write_reg(addr, *byte).await?;
+        }
+
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, 0x00).await?;
+        self.write_reg(OV5640_REG_FW_CMD_ACK, 0x00).await?;
+        self.write_reg(OV5640_REG_FW_CMD_PARA0, 0x00).await?;
+        self.write_reg(OV5640_REG_FW_CMD_PARA1, 0x00).await?;
+        self.write_reg(OV5640_REG_FW_STATUS, 0x00).await?;
+
+        self.write_reg(OV5640_REG_SYSTEM_RESET00, 0x00).await?;
+
+        for _ in 0..OV5640_AF_FW_READY_POLL_ATTEMPTS {
+            if self.read_reg(OV5640_REG_FW_STATUS).await? == OV5640_FW_STATUS_READY {
+                return Ok(());
+            }
+        }
+
+        Err(SccbError::AfFirmwareTimeout)
+    }
+
+    /// Trigger a single auto-focus pass. Returns as soon as the command is written; it does not
+    /// wait for the MCU to acknowledge it.
+    pub async fn trigger_single_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_ACK, 0x01).await?;
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_TRIGGER_SINGLE_FOCUS)
+            .await
+    }
+
+    /// Hand focus tracking over to the MCU until [`Ov5640Async::pause_focus`] is called.
+    pub async fn continuous_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_CONTINUOUS_FOCUS)
+            .await
+    }
+
+    /// Pause whatever focus mode is currently running.
+    pub async fn pause_focus(&mut self) -> Result<(), SccbError<E>> {
+        self.write_reg(OV5640_REG_FW_CMD_MAIN, OV5640_AF_CMD_PAUSE_FOCUS)
+            .await
+    }
+
+    /// Whether the MCU currently considers the image in focus.
+    pub async fn focus_status(&mut self) -> Result<bool, SccbError<E>> {
+        Ok(self.read_reg(OV5640_REG_FW_STATUS).await? == OV5640_FW_STATUS_FOCUSED)
+    }
+
+    async fn write_reg(&mut self, reg: u16, val: u8) -> Result<(), SccbError<E>> {
+        self.i2c
+            .write(
+                OV5640_ADDR,
+                &[
+                    (reg >> 8).try_into().unwrap(),
+                    (reg & 0xff).try_into().unwrap(),
+                    val,
+                ],
+            )
+            .await
+            .map_err(|e| SccbError::I2c(e))
+    }
+
+    async fn read_reg(&mut self, reg: u16) -> Result<u8, SccbError<E>> {
+        self.i2c
+            .write(
+                OV5640_ADDR,
+                &[
+                    (reg >> 8).try_into().unwrap(),
+                    (reg & 0xff).try_into().unwrap(),
+                ],
+            )
+            .await
+            .map_err(|e| SccbError::I2c(e))?;
+
+        let mut buf: [u8; 1] = [0];
+
+        self.i2c
+            .read(OV5640_ADDR, &mut buf)
+            .await
+            .map_err(|e| SccbError::I2c(e))?;
+
+        Ok(buf[0])
+    }
+
+    /// Read `reg`, clear `clear_mask`, set `set_mask`, and write the result back, so independent
+    /// bitfields sharing a register don't clobber each other.
+    async fn modify_reg(&mut self, reg: u16, clear_mask: u8, set_mask: u8) -> Result<(), SccbError<E>> {
+        let val = self.read_reg(reg).await?;
+        self.write_reg(reg, (val & !clear_mask) | set_mask).await
+    }
+
+    /// Set or clear the bits in `mask` within `reg`, leaving every other bit untouched.
+    async fn update_bits(&mut self, reg: u16, mask: u8, set: bool) -> Result<(), SccbError<E>> {
+        if set {
+            self.modify_reg(reg, 0, mask).await
+        } else {
+            self.modify_reg(reg, mask, 0).await
+        }
+    }
+
+    pub fn free(self) -> (I2C, PWDN, RST) {
+        (self.i2c, self.pwdn, self.rst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_order_flip_round_trips() {
+        for order in [
+            RawOrder::SBGGR8,
+            RawOrder::SGBRG8,
+            RawOrder::SGRBG8,
+            RawOrder::SRGGB8,
+        ] {
+            assert_eq!(order.flipped().flipped(), order);
+            assert_eq!(order.mirrored().mirrored(), order);
+        }
+    }
+
+    #[test]
+    fn raw_order_flip_and_mirror_are_distinct() {
+        // flipping or mirroring should actually change the order, never a no-op
+        for order in [
+            RawOrder::SBGGR8,
+            RawOrder::SGBRG8,
+            RawOrder::SGRBG8,
+            RawOrder::SRGGB8,
+        ] {
+            assert_ne!(order.flipped(), order);
+            assert_ne!(order.mirrored(), order);
+        }
+    }
+
+    fn assert_pclk_within_one_percent(xclk_hz: u32, target_pclk_hz: u64) {
+        let solution =
+            solve_pll(xclk_hz, target_pclk_hz).expect("expected a reachable PLL solution");
+        let achieved = solution.pclk_hz(xclk_hz);
+        let error = achieved.abs_diff(target_pclk_hz);
+        assert!(
+            error * 100 <= target_pclk_hz,
+            "pclk {achieved} too far from target {target_pclk_hz} (xclk {xclk_hz})"
+        );
+    }
+
+    #[test]
+    fn solve_pll_hits_vga_30fps_from_24mhz_xclk() {
+        let target_pclk_hz = Resolution::Vga640_480.total_pixels() as u64 * 30;
+        assert_pclk_within_one_percent(24_000_000, target_pclk_hz);
+    }
+
+    #[test]
+    fn solve_pll_hits_720p_60fps_from_24mhz_xclk() {
+        let target_pclk_hz = Resolution::P720_1280_720.total_pixels() as u64 * 60;
+        assert_pclk_within_one_percent(24_000_000, target_pclk_hz);
+    }
+
+    #[test]
+    fn solve_pll_hits_vga_30fps_from_a_different_xclk() {
+        let target_pclk_hz = Resolution::Vga640_480.total_pixels() as u64 * 30;
+        assert_pclk_within_one_percent(12_000_000, target_pclk_hz);
+    }
+}